@@ -0,0 +1,172 @@
+// Walks an already-extracted copy of the W3C XML conformance suite and emits
+// one `#[test] fn` per `<TEST>` case, so `cargo test` gives per-case
+// pass/fail instead of the interactive binary's single summary run.
+//
+// The suite isn't vendored (it's ~10MB of third-party fixtures fetched by
+// `main.rs` at runtime), so this only has something to walk once a user has
+// downloaded and extracted a release and pointed `XMLCONF_DIR` at the
+// resulting `xmlconf/` directory. Without that, we emit an empty generated
+// file rather than failing the build.
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+// Mirrors the fields of the same name in `src/main.rs`. Duplicated because
+// `build.rs` is compiled standalone and can't depend on the binary crate.
+#[derive(Debug, Deserialize)]
+#[serde(rename = "TESTCASES")]
+struct TestCasesTier1 {
+    #[serde(rename = "@xml:base")]
+    base: Option<String>,
+    #[serde(rename = "TEST", default)]
+    tests: Option<Vec<TestCase>>,
+    #[serde(rename = "TESTCASES", default)]
+    tier_2: Vec<TestCasesTier2>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename = "TESTCASES")]
+struct TestCasesTier2 {
+    #[serde(rename = "@xml:base")]
+    base: Option<String>,
+    #[serde(rename = "TEST")]
+    tests: Vec<TestCase>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename = "TEST")]
+struct TestCase {
+    #[serde(rename = "@URI")]
+    uri: String,
+    #[serde(rename = "@ID")]
+    id: String,
+    #[serde(rename = "@TYPE")]
+    expected_outcome: TestCaseType,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+enum TestCaseType {
+    Valid,
+    Invalid,
+    NotWf,
+    Error,
+}
+
+impl TestCaseType {
+    fn as_crate_path(self) -> &'static str {
+        match self {
+            TestCaseType::Valid => "crate::TestCaseType::Valid",
+            TestCaseType::Invalid => "crate::TestCaseType::Invalid",
+            TestCaseType::NotWf => "crate::TestCaseType::NotWf",
+            TestCaseType::Error => "crate::TestCaseType::Error",
+        }
+    }
+}
+
+/// Config files known to exist relative to `xmlconf/` in the 2003-12-10
+/// release, the only one in `RELEASES` (src/main.rs) with its conf paths
+/// filled in today.
+const CONF_FILES: [&str; 5] = [
+    "sun/sun-valid.xml",
+    "sun/sun-invalid.xml",
+    "sun/sun-not-wf.xml",
+    "sun/sun-error.xml",
+    "ibm/ibm_oasis_valid.xml",
+];
+
+fn sanitize_ident(id: &str) -> String {
+    let cleaned: String = id
+        .replace('/', "_")
+        .replace('-', "_")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    format!("case_{cleaned}")
+}
+
+// Mirrors `resolve_base_dir` in `src/main.rs`: an `@xml:base` is relative to
+// its own conf file's directory, and accumulates across nested `TESTCASES`.
+fn resolve_base_dir(parent_dir: &Path, xml_base: &Option<String>) -> PathBuf {
+    match xml_base {
+        Some(xml_base) => parent_dir.join(xml_base),
+        None => parent_dir.to_path_buf(),
+    }
+}
+
+fn collect_cases(conf_path: &Path, base_dir: &Path, out: &mut Vec<(String, PathBuf, TestCaseType)>) {
+    let Ok(file) = fs::File::open(conf_path) else {
+        return;
+    };
+    let Ok(tier_1): Result<TestCasesTier1, _> = quick_xml::de::from_reader(BufReader::new(file)) else {
+        println!("cargo:warning=failed to parse conformance conf file {conf_path:?}");
+        return;
+    };
+
+    let tier_1_base_dir = resolve_base_dir(base_dir, &tier_1.base);
+    for test_case in tier_1.tests.into_iter().flatten() {
+        out.push((
+            test_case.id.clone(),
+            tier_1_base_dir.join(&test_case.uri),
+            test_case.expected_outcome,
+        ));
+    }
+    for tier_2 in tier_1.tier_2 {
+        let tier_2_base_dir = resolve_base_dir(&tier_1_base_dir, &tier_2.base);
+        for test_case in tier_2.tests {
+            out.push((
+                test_case.id.clone(),
+                tier_2_base_dir.join(&test_case.uri),
+                test_case.expected_outcome,
+            ));
+        }
+    }
+}
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR set by cargo");
+    let dest_path = Path::new(&out_dir).join("generated_conformance_tests.rs");
+    println!("cargo:rerun-if-env-changed=XMLCONF_DIR");
+
+    let mut generated = String::new();
+    let mut seen_ids = HashSet::new();
+
+    if let Ok(xmlconf_dir) = env::var("XMLCONF_DIR") {
+        let xmlconf_dir = PathBuf::from(xmlconf_dir);
+        println!("cargo:rerun-if-changed={}", xmlconf_dir.display());
+
+        let mut cases = Vec::new();
+        for conf_sub_path in CONF_FILES {
+            let conf_path = xmlconf_dir.join(conf_sub_path);
+            let Some(base_dir) = conf_path.parent() else {
+                continue;
+            };
+            collect_cases(&conf_path, base_dir, &mut cases);
+        }
+
+        for (id, file_path, expected_outcome) in cases {
+            let fn_name = sanitize_ident(&id);
+            if !seen_ids.insert(fn_name.clone()) {
+                println!("cargo:warning=duplicate conformance test id {id:?} (generated fn name {fn_name:?}), skipping");
+                continue;
+            }
+            let file_path = file_path.display();
+            generated.push_str(&format!(
+                "#[test]\nfn {fn_name}() {{\n    crate::conformance::assert_case_outcome(\n        {id:?},\n        std::path::Path::new(r\"{file_path}\"),\n        {expected},\n    );\n}}\n\n",
+                expected = expected_outcome.as_crate_path(),
+            ));
+        }
+    } else {
+        println!(
+            "cargo:warning=XMLCONF_DIR not set; no per-case conformance tests were generated (see build.rs)"
+        );
+    }
+
+    fs::File::create(&dest_path)
+        .and_then(|mut f| f.write_all(generated.as_bytes()))
+        .expect("writing generated_conformance_tests.rs");
+}
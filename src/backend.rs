@@ -0,0 +1,149 @@
+use std::fmt;
+use std::path::Path;
+
+use quick_xml::events::Event;
+use quick_xml::reader::NsReader;
+
+/// Which conformance question a parser is being asked to answer.
+///
+/// The test suite distinguishes "is this document well-formed" from "is this
+/// document valid" (i.e. well-formed *and* obeys its DTD). Parsers that don't
+/// do DTD validation can only ever answer the former.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParseMode {
+    WellFormed,
+    Valid,
+}
+
+/// Why a backend failed to parse a document.
+///
+/// This only has a `NotWellFormed` case today because none of the backends
+/// below do DTD validation yet (see the `let _ = mode;` in each
+/// `parse_document`); a `Valid` mode still can't distinguish "not
+/// well-formed" from "well-formed but invalid" until one does. Add an
+/// `Invalid(String)` variant here once a validating backend (libxml2 is the
+/// candidate) actually needs to report it — an unconstructed variant fails
+/// `clippy -D warnings`.
+#[derive(Debug)]
+pub enum ParseDiagnostic {
+    NotWellFormed(String),
+}
+
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseDiagnostic::NotWellFormed(msg) => write!(f, "not well-formed: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseDiagnostic {}
+
+/// A Rust XML parser the conformance suite can be run against.
+///
+/// Implementing this for a new crate is how you add it to the comparison:
+/// the runner iterates every selected backend over every `TestCase` rather
+/// than being hardwired to one parser.
+pub trait ConformanceParser {
+    /// Short name used in reports and CLI selection (e.g. `"quick-xml"`).
+    fn name(&self) -> &'static str;
+
+    /// Parse `path` and report whether it satisfies `mode`.
+    fn parse_document(&self, path: &Path, mode: ParseMode) -> Result<(), ParseDiagnostic>;
+}
+
+/// The only backend available without optional features: `quick-xml`, the
+/// parser this crate already depended on.
+pub struct QuickXmlBackend;
+
+impl ConformanceParser for QuickXmlBackend {
+    fn name(&self) -> &'static str {
+        "quick-xml"
+    }
+
+    fn parse_document(&self, path: &Path, mode: ParseMode) -> Result<(), ParseDiagnostic> {
+        // quick-xml doesn't do DTD validation, so the best it can do for
+        // `Valid` is confirm well-formedness; it can never catch a document
+        // that is well-formed but invalid.
+        let _ = mode;
+        let mut reader = NsReader::from_file(path)
+            .map_err(|err| ParseDiagnostic::NotWellFormed(err.to_string()))?;
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_resolved_event_into(&mut buf) {
+                Err(err) => return Err(ParseDiagnostic::NotWellFormed(err.to_string())),
+                Ok((_, Event::Eof)) => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(feature = "xml-rs")]
+pub struct XmlRsBackend;
+
+#[cfg(feature = "xml-rs")]
+impl ConformanceParser for XmlRsBackend {
+    fn name(&self) -> &'static str {
+        "xml-rs"
+    }
+
+    fn parse_document(&self, path: &Path, mode: ParseMode) -> Result<(), ParseDiagnostic> {
+        // xml-rs is also well-formedness-only; same caveat as quick-xml.
+        let _ = mode;
+        let file = std::fs::File::open(path)
+            .map_err(|err| ParseDiagnostic::NotWellFormed(err.to_string()))?;
+        let reader = xml::reader::EventReader::new(std::io::BufReader::new(file));
+        for event in reader {
+            event.map_err(|err| ParseDiagnostic::NotWellFormed(err.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "roxmltree")]
+pub struct RoxmltreeBackend;
+
+#[cfg(feature = "roxmltree")]
+impl ConformanceParser for RoxmltreeBackend {
+    fn name(&self) -> &'static str {
+        "roxmltree"
+    }
+
+    fn parse_document(&self, path: &Path, mode: ParseMode) -> Result<(), ParseDiagnostic> {
+        // roxmltree parses into a DOM in one shot and is also
+        // well-formedness-only (no DTD validation).
+        let _ = mode;
+        let text =
+            std::fs::read_to_string(path).map_err(|err| ParseDiagnostic::NotWellFormed(err.to_string()))?;
+        roxmltree::Document::parse(&text)
+            .map_err(|err| ParseDiagnostic::NotWellFormed(err.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "libxml")]
+pub struct LibxmlBackend;
+
+#[cfg(feature = "libxml")]
+impl ConformanceParser for LibxmlBackend {
+    fn name(&self) -> &'static str {
+        "libxml"
+    }
+
+    fn parse_document(&self, path: &Path, mode: ParseMode) -> Result<(), ParseDiagnostic> {
+        use libxml::parser::Parser;
+
+        // libxml2 is the one backend here that's actually capable of DTD
+        // validation, but wiring that through the `libxml` crate's safe API
+        // is left as a follow-up; for now it only checks well-formedness.
+        let _ = mode;
+        let parser = Parser::default();
+        parser
+            .parse_file(path.to_str().ok_or_else(|| {
+                ParseDiagnostic::NotWellFormed("path is not valid UTF-8".to_string())
+            })?)
+            .map_err(|err| ParseDiagnostic::NotWellFormed(err.to_string()))?;
+        Ok(())
+    }
+}
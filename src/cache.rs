@@ -0,0 +1,172 @@
+//! Download caching for conformance test suite releases: verifies a known
+//! SHA-256 before extracting (when one is on record — see `RELEASES` in
+//! `main.rs`) and memoizes config files read by
+//! `run_sun_tests_for`/`run_ibm_tests_for`.
+//!
+//! `SourceRoot::Zip` holds the downloaded bytes between `ensure_downloaded`
+//! and `ensure_extracted`; it is not a general "read files straight out of
+//! the archive" path. `ensure_extracted` always extracts to disk and
+//! replaces it with `SourceRoot::Directory`, because `ConformanceParser::
+//! parse_document` takes a `&Path` and needs a real file to read — wiring up
+//! in-memory reads for actual test case files would mean changing that
+//! trait, which is out of scope here.
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::ConformanceTestRelease;
+
+#[derive(Debug)]
+pub enum CacheError {
+    Io(std::io::Error),
+    Http(reqwest::Error),
+    Zip(zip::result::ZipError),
+    ChecksumMismatch { release: String, expected: String, actual: String },
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Io(err) => write!(f, "{err}"),
+            CacheError::Http(err) => write!(f, "{err}"),
+            CacheError::Zip(err) => write!(f, "{err}"),
+            CacheError::ChecksumMismatch { release, expected, actual } => write!(
+                f,
+                "checksum mismatch for release {release}: expected {expected}, got {actual} (download is corrupt or truncated)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {}
+
+impl From<std::io::Error> for CacheError {
+    fn from(err: std::io::Error) -> Self {
+        CacheError::Io(err)
+    }
+}
+
+impl From<reqwest::Error> for CacheError {
+    fn from(err: reqwest::Error) -> Self {
+        CacheError::Http(err)
+    }
+}
+
+impl From<zip::result::ZipError> for CacheError {
+    fn from(err: zip::result::ZipError) -> Self {
+        CacheError::Zip(err)
+    }
+}
+
+/// Where a release's files currently live.
+enum SourceRoot {
+    /// Already extracted onto disk, rooted at this directory.
+    Directory(PathBuf),
+    /// Downloaded and checksummed, but not yet extracted to disk.
+    Zip(zip::ZipArchive<Cursor<Vec<u8>>>),
+}
+
+/// Owns the download/verify/extract lifecycle for conformance test suite
+/// releases, and memoizes config file text so the same file isn't re-read
+/// from disk. Only the text is cached: each runner still deserializes it.
+pub struct SourceCache {
+    base_dir: PathBuf,
+    roots: HashMap<String, SourceRoot>,
+    config_text_cache: HashMap<PathBuf, String>,
+}
+
+impl SourceCache {
+    pub fn new(base_dir: PathBuf) -> Self {
+        Self {
+            base_dir,
+            roots: HashMap::new(),
+            config_text_cache: HashMap::new(),
+        }
+    }
+
+    /// Download (if not already on disk) and checksum-verify `release`,
+    /// caching it as an in-memory zip until something asks to extract it.
+    pub async fn ensure_downloaded(&mut self, release: &ConformanceTestRelease<'_>) -> Result<(), CacheError> {
+        if self.roots.contains_key(release.filename) {
+            return Ok(());
+        }
+
+        let zip_path = self.base_dir.join(release.filename);
+        let bytes = if zip_path.try_exists()? {
+            fs::read(&zip_path)?
+        } else {
+            let response = reqwest::get(release.download_zip_url).await?;
+            let bytes = response.bytes().await?.to_vec();
+            fs::write(&zip_path, &bytes)?;
+            bytes
+        };
+
+        if let Some(expected_sha256) = release.sha256 {
+            verify_checksum(release.release_date, &bytes, expected_sha256)?;
+        } else {
+            eprintln!(
+                "warning: no recorded SHA-256 for release {}; skipping checksum verification (see `sha256` on ConformanceTestRelease in main.rs)",
+                release.release_date
+            );
+        }
+
+        let archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+        self.roots.insert(release.filename.to_string(), SourceRoot::Zip(archive));
+        Ok(())
+    }
+
+    /// Extract `release` to disk (if it hasn't been already) and return the
+    /// root of its `xmlconf/` tree. Parsers need real file paths, so this is
+    /// still required before running any test cases.
+    pub fn ensure_extracted(&mut self, release: &ConformanceTestRelease<'_>) -> Result<PathBuf, CacheError> {
+        let zip_path = self.base_dir.join(release.filename);
+        let extract_dir = self
+            .base_dir
+            .join(zip_path.file_stem().unwrap_or_default());
+
+        if !extract_dir.try_exists()? {
+            if let Some(SourceRoot::Zip(archive)) = self.roots.get_mut(release.filename) {
+                archive.extract(&extract_dir)?;
+            } else {
+                let file = File::open(&zip_path)?;
+                let mut archive = zip::ZipArchive::new(file)?;
+                archive.extract(&extract_dir)?;
+            }
+        }
+        self.roots
+            .insert(release.filename.to_string(), SourceRoot::Directory(extract_dir.clone()));
+        Ok(extract_dir.join("xmlconf"))
+    }
+
+    /// Read a config file's contents as a string, memoized by `conf_path` as
+    /// given so the same conf file is read from disk once. The text is not
+    /// parsed here; callers deserialize it on every call.
+    /// `conf_path` must already be extracted (see `ensure_extracted`);
+    /// `SourceRoot` covers the download/extract stage, not this lookup.
+    pub fn read_config_text(&mut self, conf_path: &Path) -> Result<&str, CacheError> {
+        if !self.config_text_cache.contains_key(conf_path) {
+            let text = fs::read_to_string(conf_path)?;
+            self.config_text_cache.insert(conf_path.to_path_buf(), text);
+        }
+        Ok(self.config_text_cache.get(conf_path).expect("just inserted"))
+    }
+}
+
+fn verify_checksum(release_date: &str, bytes: &[u8], expected_sha256: &str) -> Result<(), CacheError> {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    let actual = format!("{:x}", hasher.finalize());
+    if actual.eq_ignore_ascii_case(expected_sha256) {
+        Ok(())
+    } else {
+        Err(CacheError::ChecksumMismatch {
+            release: release_date.to_string(),
+            expected: expected_sha256.to_string(),
+            actual,
+        })
+    }
+}
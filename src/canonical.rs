@@ -0,0 +1,225 @@
+//! Canonical-XML (James Clark's form, as used by the OASIS/W3C conformance
+//! suite's `@OUTPUT` reference files) serialization and comparison.
+//!
+//! This crate has no DTD support, so two things real canonical XML requires
+//! are missing here: general entities declared in a DTD aren't expanded,
+//! and `#FIXED`/`#IMPLIED` default attribute values a DTD declares are never
+//! materialized. `canonicalize_file` flags the latter gap via
+//! [`Canonicalized::has_doctype`] so callers can skip rather than misreport
+//! a DTD-driven difference as a parser bug.
+use std::fmt;
+use std::path::Path;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+
+#[derive(Debug)]
+pub enum CanonicalError {
+    Parse(String),
+}
+
+impl fmt::Display for CanonicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CanonicalError::Parse(msg) => write!(f, "canonicalization failed: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for CanonicalError {}
+
+/// Output of [`canonicalize_file`].
+pub struct Canonicalized {
+    pub text: String,
+    /// Whether the document had a `DOCTYPE` declaration.
+    ///
+    /// This crate has no DTD support, so `text` never materializes
+    /// `#FIXED`/`#IMPLIED` default attribute values a DTD would supply, the
+    /// way real canonical XML requires. Callers comparing `text` against an
+    /// `@OUTPUT` reference should treat `has_doctype` documents as unable to
+    /// give a reliable answer, not as genuine mismatches.
+    pub has_doctype: bool,
+}
+
+/// Serialize the document at `path` into the test suite's canonical form:
+/// XML/DOCTYPE declarations and comments dropped, CDATA sections replaced by
+/// their text, entity and character references expanded, empty elements
+/// written as separate start/end tags, and attributes sorted lexicographically
+/// by name.
+///
+/// Note: this only expands the built-in and numeric character references
+/// quick-xml resolves for us; general entities declared in a DTD are not
+/// expanded, and DTD-defaulted attributes are never materialized, since this
+/// crate has no DTD support. See [`Canonicalized::has_doctype`].
+pub fn canonicalize_file(path: &Path) -> Result<Canonicalized, CanonicalError> {
+    let mut reader =
+        Reader::from_file(path).map_err(|err| CanonicalError::Parse(err.to_string()))?;
+    reader.config_mut().trim_text(false);
+
+    let mut buf = Vec::new();
+    let mut out = String::new();
+    let mut has_doctype = false;
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|err| CanonicalError::Parse(err.to_string()))?;
+        match event {
+            Event::DocType(_) => has_doctype = true,
+            Event::Decl(_) | Event::PI(_) | Event::Comment(_) => {}
+            Event::Start(start) => write_start_tag(&mut out, &start)?,
+            Event::Empty(start) => {
+                write_start_tag(&mut out, &start)?;
+                write_end_tag(&mut out, &start)?;
+            }
+            Event::End(end) => {
+                out.push_str("</");
+                out.push_str(
+                    std::str::from_utf8(end.name().as_ref())
+                        .map_err(|err| CanonicalError::Parse(err.to_string()))?,
+                );
+                out.push('>');
+            }
+            Event::Text(text) => {
+                let decoded = text
+                    .unescape()
+                    .map_err(|err| CanonicalError::Parse(err.to_string()))?;
+                escape_text_into(&mut out, &decoded);
+            }
+            Event::CData(cdata) => {
+                let decoded = std::str::from_utf8(cdata.as_ref())
+                    .map_err(|err| CanonicalError::Parse(err.to_string()))?;
+                escape_text_into(&mut out, decoded);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(Canonicalized { text: out, has_doctype })
+}
+
+fn write_start_tag(out: &mut String, start: &BytesStart) -> Result<(), CanonicalError> {
+    out.push('<');
+    out.push_str(
+        std::str::from_utf8(start.name().as_ref())
+            .map_err(|err| CanonicalError::Parse(err.to_string()))?,
+    );
+
+    let mut attrs = Vec::new();
+    for attr in start.attributes() {
+        let attr = attr.map_err(|err| CanonicalError::Parse(err.to_string()))?;
+        let key = std::str::from_utf8(attr.key.as_ref())
+            .map_err(|err| CanonicalError::Parse(err.to_string()))?
+            .to_string();
+        let value = attr
+            .unescape_value()
+            .map_err(|err| CanonicalError::Parse(err.to_string()))?
+            .into_owned();
+        attrs.push((key, value));
+    }
+    attrs.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (key, value) in attrs {
+        out.push(' ');
+        out.push_str(&key);
+        out.push_str("=\"");
+        escape_attr_into(out, &value);
+        out.push('"');
+    }
+    out.push('>');
+    Ok(())
+}
+
+fn write_end_tag(out: &mut String, start: &BytesStart) -> Result<(), CanonicalError> {
+    out.push_str("</");
+    out.push_str(
+        std::str::from_utf8(start.name().as_ref())
+            .map_err(|err| CanonicalError::Parse(err.to_string()))?,
+    );
+    out.push('>');
+    Ok(())
+}
+
+/// Escapes `&`, `<`, `>` and `\r` the way the canonical form requires in
+/// element content. Tabs and newlines are left literal: only attribute
+/// values get them escaped as character references.
+fn escape_text_into(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\r' => out.push_str("&#13;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// Escapes `&`, `<`, `>`, `"` and tab/newline/CR the way the canonical form
+/// requires in attribute values.
+fn escape_attr_into(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\t' => out.push_str("&#9;"),
+            '\n' => out.push_str("&#10;"),
+            '\r' => out.push_str("&#13;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// Compares two canonicalized documents, returning `None` if they're
+/// byte-for-byte equal or `Some(diff)` otherwise, listing every line that
+/// differs among the lines both have and noting any difference in line count.
+pub fn diff_canonical(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    let mut report = String::new();
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    for (i, pair) in expected_lines
+        .iter()
+        .zip(actual_lines.iter())
+        .enumerate()
+        .filter(|(_, (e, a))| e != a)
+    {
+        let (expected_line, actual_line) = pair;
+        report.push_str(&format!(
+            "line {}: expected {expected_line:?}, got {actual_line:?}\n",
+            i + 1
+        ));
+    }
+    if expected_lines.len() != actual_lines.len() {
+        report.push_str(&format!(
+            "expected {} lines, got {} lines\n",
+            expected_lines.len(),
+            actual_lines.len()
+        ));
+    }
+    Some(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn text_content_newlines_and_quotes_survive() {
+        let mut file = tempfile::Builder::new()
+            .suffix(".xml")
+            .tempfile()
+            .unwrap();
+        write!(file, "<a>\n  text \"quoted\"\n</a>").unwrap();
+
+        let out = canonicalize_file(file.path()).unwrap();
+        assert_eq!(out.text, "<a>\n  text \"quoted\"\n</a>");
+        assert!(!out.has_doctype);
+    }
+}
@@ -0,0 +1,116 @@
+//! Non-interactive CLI: selecting releases by date, filtering cases by
+//! type/section/recommendation/version/edition/namespace, and choosing an
+//! output directory and report format — all scriptable, without the
+//! `inquire::MultiSelect` prompt.
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::report::ReportFormat;
+use crate::TestCaseType;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Run the W3C XML conformance test suite against one or more Rust XML parsers")]
+pub struct Cli {
+    /// Release dates to run, e.g. `--release 2003-12-10`. Repeatable.
+    /// If omitted, prompts interactively for which releases to run.
+    #[arg(long = "release")]
+    pub releases: Vec<String>,
+
+    /// Restrict to cases with one of these expected outcome types.
+    /// Repeatable; if omitted, all types run.
+    #[arg(long = "type", value_enum)]
+    pub types: Vec<TestCaseType>,
+
+    /// Restrict to cases whose `@SECTIONS` contains this substring.
+    #[arg(long)]
+    pub sections: Option<String>,
+
+    /// Restrict to cases with this exact `@RECOMMENDATION`.
+    #[arg(long)]
+    pub recommendation: Option<String>,
+
+    /// Restrict to cases whose (possibly space-separated list of) `@VERSION`
+    /// values includes this one.
+    #[arg(long)]
+    pub version: Option<String>,
+
+    /// Restrict to cases whose (possibly space-separated list of) `@EDITION`
+    /// values includes this one.
+    #[arg(long)]
+    pub edition: Option<String>,
+
+    /// Restrict to cases with this exact `@NAMESPACE`.
+    #[arg(long)]
+    pub namespace: Option<String>,
+
+    /// Directory to download and extract test suites into.
+    #[arg(long, default_value = ".")]
+    pub out_dir: PathBuf,
+
+    /// Write a machine-readable report in this format instead of printing
+    /// mismatches to stdout.
+    #[arg(long, value_enum)]
+    pub report: Option<ReportFormat>,
+
+    /// Path for `--report` output (default: `report.json` / `report.xml`).
+    #[arg(long)]
+    pub report_out: Option<PathBuf>,
+}
+
+impl Cli {
+    pub fn report_path(&self) -> PathBuf {
+        if let Some(path) = &self.report_out {
+            return path.clone();
+        }
+        match self.report {
+            Some(ReportFormat::Json) | None => PathBuf::from("report.json"),
+            Some(ReportFormat::JUnit) => PathBuf::from("report.xml"),
+        }
+    }
+
+    pub fn matches(&self, test_case: &crate::TestCase) -> bool {
+        if !self.types.is_empty() && !self.types.contains(&test_case.expected_outcome) {
+            return false;
+        }
+        if let Some(sections) = &self.sections {
+            if !test_case.sections.contains(sections.as_str()) {
+                return false;
+            }
+        }
+        if !matches_opt(&self.recommendation, &test_case.recommendation) {
+            return false;
+        }
+        if !matches_list_opt(&self.version, &test_case.version) {
+            return false;
+        }
+        if !matches_list_opt(&self.edition, &test_case.edition) {
+            return false;
+        }
+        if !matches_opt(&self.namespace, &test_case.namespace) {
+            return false;
+        }
+        true
+    }
+}
+
+/// `true` if there's no filter, or the filter equals the case's attribute.
+fn matches_opt(filter: &Option<String>, value: &Option<String>) -> bool {
+    match filter {
+        None => true,
+        Some(filter) => value.as_deref() == Some(filter.as_str()),
+    }
+}
+
+/// `true` if there's no filter, or the filter is one of the whitespace-
+/// separated values in the case's attribute (e.g. `EDITION="2 3 4 5"` means
+/// the case applies to editions 2 through 5, not to the literal string
+/// `"2 3 4 5"`).
+fn matches_list_opt(filter: &Option<String>, value: &Option<String>) -> bool {
+    match filter {
+        None => true,
+        Some(filter) => value
+            .as_deref()
+            .is_some_and(|value| value.split_whitespace().any(|v| v == filter)),
+    }
+}
@@ -0,0 +1,34 @@
+//! Shared assertion used by the `#[test]` functions `build.rs` generates
+//! into `generated_tests` (one per conformance case).
+use std::path::Path;
+
+use crate::backend::{ConformanceParser, ParseMode, QuickXmlBackend};
+use crate::TestCaseType;
+
+/// Parse `path` with the default backend and panic if the outcome doesn't
+/// match `expected`, the way a regular test assertion would.
+pub(crate) fn assert_case_outcome(id: &str, path: &Path, expected: TestCaseType) {
+    let mode = match expected {
+        TestCaseType::Valid | TestCaseType::Invalid => ParseMode::Valid,
+        TestCaseType::NotWf | TestCaseType::Error => ParseMode::WellFormed,
+    };
+    let result = QuickXmlBackend.parse_document(path, mode);
+    match expected {
+        TestCaseType::Valid => assert!(
+            result.is_ok(),
+            "test case {id} ({path:?}) expected Valid but got {result:?}"
+        ),
+        TestCaseType::Invalid => {
+            // quick-xml can't do DTD validation, so this can only ever
+            // confirm well-formedness; an `Invalid` case that's merely
+            // well-formed doesn't count as a failure here.
+            if let Err(err) = result {
+                panic!("test case {id} ({path:?}) expected Invalid but wasn't well-formed: {err}");
+            }
+        }
+        TestCaseType::NotWf | TestCaseType::Error => assert!(
+            result.is_err(),
+            "test case {id} ({path:?}) expected {expected:?} but parsed successfully"
+        ),
+    }
+}
@@ -1,32 +1,39 @@
+mod backend;
+mod cache;
+mod canonical;
+mod cli;
+mod conformance;
+mod report;
+
 use std::fmt::Display;
 
 use clap::Parser;
-use quick_xml::events::Event;
 use tempfile::Builder;
 use inquire::{MultiSelect};
-use std::fs::{File, OpenOptions};
-use std::io::{copy, Cursor, BufReader};
 use std::{thread, time};
-use std::env;
 use std::path::{PathBuf, Path};
 use serde::Deserialize;
 
-use quick_xml::de::from_reader;
-use quick_xml::reader::NsReader;
-
-#[derive(Parser)]
-struct Cli {
-    /// The pattern to look for
-    pattern: String,
-    /// The path to the file to read
-    path: std::path::PathBuf,
-}
+use backend::{ConformanceParser, ParseMode, QuickXmlBackend};
+use cache::SourceCache;
+use cli::Cli;
+use report::{CaseResult, ReportAccumulator};
 
 #[derive(Clone)]
 struct ConformanceTestRelease<'a> {
     release_date: &'a str,
     download_zip_url: &'a str,
     filename: &'a str,
+    /// Known-good SHA-256 of the zip, checked before extracting. `None`
+    /// means we haven't recorded one for this release yet, in which case
+    /// `SourceCache` skips verification (and prints a warning rather than
+    /// silently doing nothing — see `ensure_downloaded`).
+    ///
+    /// TODO: every release below is currently `None`. Fill one in by
+    /// downloading it and running `sha256sum xmlts20031210.zip` (or
+    /// equivalent for the other releases), then paste the digest here so
+    /// verification is actually live for that release.
+    sha256: Option<&'a str>,
     sun_valid_tests_conf: Option<&'a str>,
     sun_invalid_tests_conf: Option<&'a str>,
     sun_non_wf_tests_conf: Option<&'a str>,
@@ -41,14 +48,13 @@ impl Display for ConformanceTestRelease<'_> {
 }
 
 // Without xsl + dtd support, we have to hardcode the info contained :(
-// in these tests. If we had xsl + dtd support, we could use the best xml
-// parser on the rust market to download and parse the test cases and finally
-// run the tests on the xml parser itself (in addition to other xml parsers)!
+// in these tests.
 const RELEASES: [ConformanceTestRelease<'static>; 4] = [
-    ConformanceTestRelease { 
-        release_date: "2003-12-10", 
-        download_zip_url: "https://www.w3.org/XML/Test/xmlts20031210.zip", 
+    ConformanceTestRelease {
+        release_date: "2003-12-10",
+        download_zip_url: "https://www.w3.org/XML/Test/xmlts20031210.zip",
         filename: "xmlts20031210.zip",
+        sha256: None,
         sun_valid_tests_conf: Some("sun/sun-valid.xml"),
         sun_invalid_tests_conf: Some("sun/sun-invalid.xml"),
         sun_non_wf_tests_conf: Some("sun/sun-not-wf.xml"),
@@ -59,6 +65,7 @@ const RELEASES: [ConformanceTestRelease<'static>; 4] = [
         release_date: "2008-02-05", 
         download_zip_url: "https://www.w3.org/XML/Test/xmlts20080205.zip", 
         filename: "xmlts20080205.zip",
+        sha256: None,
         sun_valid_tests_conf: None,
         sun_invalid_tests_conf: None,
         sun_non_wf_tests_conf: None,
@@ -69,6 +76,7 @@ const RELEASES: [ConformanceTestRelease<'static>; 4] = [
         release_date: "2008-08-27", 
         download_zip_url: "https://www.w3.org/XML/Test/xmlts20080827.zip", 
         filename: "xmlts20080827.zip",
+        sha256: None,
         sun_valid_tests_conf: None,
         sun_invalid_tests_conf: None,
         sun_non_wf_tests_conf: None,
@@ -79,6 +87,7 @@ const RELEASES: [ConformanceTestRelease<'static>; 4] = [
         release_date: "2013-09-23", 
         download_zip_url: "https://www.w3.org/XML/Test/xmlts20130923.zip", 
         filename: "xmlts20130923.zip",
+        sha256: None,
         sun_valid_tests_conf: None,
         sun_invalid_tests_conf: None,
         sun_non_wf_tests_conf: None,
@@ -123,6 +132,14 @@ struct TestCase {
     entities: Option<String>,
     #[serde(rename = "@SECTIONS")]
     sections: String,
+    #[serde(rename = "@RECOMMENDATION")]
+    recommendation: Option<String>,
+    #[serde(rename = "@VERSION")]
+    version: Option<String>,
+    #[serde(rename = "@EDITION")]
+    edition: Option<String>,
+    #[serde(rename = "@NAMESPACE")]
+    namespace: Option<String>,
     #[serde(rename = "@TYPE")]
     expected_outcome: TestCaseType,
     #[serde(rename = "@OUTPUT")]
@@ -130,8 +147,9 @@ struct TestCase {
     #[serde(rename = "$text")]
     test_comment: String,
 }
-#[derive(PartialEq, Debug, Deserialize)]
+#[derive(PartialEq, Clone, Copy, Debug, Deserialize, clap::ValueEnum)]
 #[serde(rename_all = "kebab-case")]
+#[value(rename_all = "kebab-case")]
 enum TestCaseType {
     Valid,
     Invalid,
@@ -139,100 +157,247 @@ enum TestCaseType {
     Error,
 }
 
+/// The set of `ConformanceParser` backends this build was compiled with.
+/// `quick-xml` is always available; the rest are opt-in via Cargo features
+/// so users aren't forced to pull in every XML crate in the ecosystem.
+fn selected_backends() -> Vec<Box<dyn ConformanceParser>> {
+    let mut backends: Vec<Box<dyn ConformanceParser>> = vec![Box::new(QuickXmlBackend)];
+    #[cfg(feature = "xml-rs")]
+    backends.push(Box::new(backend::XmlRsBackend));
+    #[cfg(feature = "roxmltree")]
+    backends.push(Box::new(backend::RoxmltreeBackend));
+    #[cfg(feature = "libxml")]
+    backends.push(Box::new(backend::LibxmlBackend));
+    backends
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    //let args = Cli::parse();
-    let selected_releases = MultiSelect::new("Select which test suites to run:", RELEASES.to_vec()).prompt()?;
-    
+    let cli = Cli::parse();
+    let selected_releases = if cli.releases.is_empty() {
+        MultiSelect::new("Select which test suites to run:", RELEASES.to_vec()).prompt()?
+    } else {
+        RELEASES
+            .iter()
+            .filter(|release| cli.releases.iter().any(|date| date == release.release_date))
+            .cloned()
+            .collect()
+    };
+    let backends = selected_backends();
+    let mut report_accumulator = ReportAccumulator::new();
+
     //let tmp_dir = Builder::new().prefix("example").tempdir()?;
-    let curr_dir = std::env::current_dir()?;
-    let curr_dir_path = curr_dir.as_path();
+    std::fs::create_dir_all(&cli.out_dir)?;
+    let mut source_cache = SourceCache::new(cli.out_dir.clone());
     for release in selected_releases.iter() {
-        let zip_file_path = curr_dir_path.join(release.filename);
-        if !zip_file_path.try_exists()? {
-            let response = reqwest::get(release.download_zip_url).await?;
-            let mut zip_file_write = File::create(&zip_file_path)?;
-            let mut content = Cursor::new(response.bytes().await?);
-            copy(&mut content, &mut zip_file_write)?;
-        }
-
-        let zip_file_parent = zip_file_path.parent().ok_or(format!("no parent for {:?}", zip_file_path.to_str()))?;
-        let extract_dir_name = zip_file_path.file_stem().ok_or(format!("no file stem for {:?}", zip_file_path.to_str()))?;
-        let extract_dir_path = zip_file_parent.join(extract_dir_name);
+        source_cache.ensure_downloaded(release).await?;
+        let release_root_path = source_cache.ensure_extracted(release)?;
 
-        if !extract_dir_path.try_exists()? {
-            let zip_file_read = File::open(&zip_file_path)?;
-            let mut archive = zip::ZipArchive::new(zip_file_read)?;
-            archive.extract(&extract_dir_path)?;
-        }
-        let release_root_path = extract_dir_path.as_path().join("xmlconf");
         if let Some(conf_sub_path) = release.sun_valid_tests_conf {
-            run_sun_tests_for(conf_sub_path, &release_root_path)?;
+            run_sun_tests_for(conf_sub_path, &release_root_path, &backends, &cli, &mut source_cache, &mut report_accumulator)?;
         }
         if let Some(conf_sub_path) = release.sun_invalid_tests_conf {
-            run_sun_tests_for(conf_sub_path, &release_root_path)?;
+            run_sun_tests_for(conf_sub_path, &release_root_path, &backends, &cli, &mut source_cache, &mut report_accumulator)?;
         }
         if let Some(conf_sub_path) = release.sun_non_wf_tests_conf {
-            run_sun_tests_for(conf_sub_path, &release_root_path)?;
+            run_sun_tests_for(conf_sub_path, &release_root_path, &backends, &cli, &mut source_cache, &mut report_accumulator)?;
         }
         if let Some(conf_sub_path) = release.sun_error_tests_conf {
-            run_sun_tests_for(conf_sub_path, &release_root_path)?;
+            run_sun_tests_for(conf_sub_path, &release_root_path, &backends, &cli, &mut source_cache, &mut report_accumulator)?;
         }
         if let Some(conf_sub_path) = release.ibm_valid_tests_conf {
-            run_ibm_tests_for(conf_sub_path, &release_root_path)?;
+            run_ibm_tests_for(conf_sub_path, &release_root_path, &backends, &cli, &mut source_cache, &mut report_accumulator)?;
         }
     }
-    
+
+    if let Some(format) = cli.report {
+        report::write_report(&report_accumulator, format, &cli.report_path())?;
+    } else {
+        for result in report_accumulator.results.iter().filter(|r| !r.passed) {
+            println!(
+                "------------------\nMISMATCHED OUTCOME ({})\nexpected {}, got {}\nFor test: {} ({})\n{}",
+                result.backend,
+                result.expected_outcome,
+                result.actual_outcome,
+                result.id,
+                result.sections,
+                result.message.as_deref().unwrap_or(""),
+            );
+        }
+    }
+
     Ok(())
 }
 
-fn run_ibm_tests_for(conf_sub_path: &str, release_root_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    let (conf_file_parent_dir, reader) = setup_config_file_buf_reader(release_root_path, conf_sub_path)?;
-    let tier_1: TestCasesTier1 = from_reader(reader)?;
+/// Joins `xml_base` (if any) onto `parent_dir`, the way `@xml:base` nests:
+/// each level resolves relative to its parent's already-resolved base.
+fn resolve_base_dir(parent_dir: &Path, xml_base: &Option<String>) -> PathBuf {
+    match xml_base {
+        Some(xml_base) => parent_dir.join(xml_base),
+        None => parent_dir.to_path_buf(),
+    }
+}
+
+fn run_ibm_tests_for(
+    conf_sub_path: &str,
+    release_root_path: &Path,
+    backends: &[Box<dyn ConformanceParser>],
+    cli: &Cli,
+    source_cache: &mut SourceCache,
+    report_accumulator: &mut ReportAccumulator,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (conf_file_parent_dir, text) = read_config_file(release_root_path, conf_sub_path, source_cache)?;
+    let tier_1: TestCasesTier1 = quick_xml::de::from_str(text)?;
+    let tier_1_base_dir = resolve_base_dir(&conf_file_parent_dir, &tier_1.base);
     for test_cases in tier_1.tier_2.iter() {
-        run_test_case_node(&test_cases.tests, &conf_file_parent_dir)?;
+        let tier_2_base_dir = resolve_base_dir(&tier_1_base_dir, &test_cases.base);
+        run_test_case_node(&test_cases.tests, &tier_2_base_dir, backends, cli, report_accumulator)?;
     }
     Ok(())
 }
 
-fn run_sun_tests_for(conf_sub_path: &str, release_root_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    let (conf_file_parent_dir, reader) = setup_config_file_buf_reader(release_root_path, conf_sub_path)?;
-    let test_cases: Vec<TestCase> = from_reader(reader)?;
-    run_test_case_node(&test_cases, &conf_file_parent_dir)?;
+fn run_sun_tests_for(
+    conf_sub_path: &str,
+    release_root_path: &Path,
+    backends: &[Box<dyn ConformanceParser>],
+    cli: &Cli,
+    source_cache: &mut SourceCache,
+    report_accumulator: &mut ReportAccumulator,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (conf_file_parent_dir, text) = read_config_file(release_root_path, conf_sub_path, source_cache)?;
+    let test_cases: TestCasesTier2 = quick_xml::de::from_str(text)?;
+    let base_dir = resolve_base_dir(&conf_file_parent_dir, &test_cases.base);
+    run_test_case_node(&test_cases.tests, &base_dir, backends, cli, report_accumulator)?;
     Ok(())
 }
 
-fn run_test_case_node(test_cases: &[TestCase], conf_file_parent_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    for test_case in test_cases.iter() {
-        let file_to_read_path = conf_file_parent_dir.join(test_case.uri.clone());
-        let mut test_case_reader = NsReader::from_file(file_to_read_path.as_path())?;
-        let mut buf = Vec::new();
-        loop {
-            let result = test_case_reader.read_resolved_event_into(&mut buf);
-            match result { 
+fn run_test_case_node(
+    test_cases: &[TestCase],
+    base_dir: &Path,
+    backends: &[Box<dyn ConformanceParser>],
+    cli: &Cli,
+    report_accumulator: &mut ReportAccumulator,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for test_case in test_cases.iter().filter(|test_case| cli.matches(test_case)) {
+        let file_to_read_path = base_dir.join(test_case.uri.clone());
+        let mode = match test_case.expected_outcome {
+            TestCaseType::Valid | TestCaseType::Invalid => ParseMode::Valid,
+            TestCaseType::NotWf | TestCaseType::Error => ParseMode::WellFormed,
+        };
+        for parser in backends.iter() {
+            match parser.parse_document(file_to_read_path.as_path(), mode) {
                 Err(err) => {
-                    if test_case.expected_outcome == TestCaseType::Valid || test_case.expected_outcome == TestCaseType::Invalid {
-                        println!("------------------\nMISMATCHED OUTCOME\nGot error: {:?}\nIn well formed test: {:?}", err, test_case);
-                    }      
-                    break;
-                },
-                Ok((_, Event::Eof)) => {
-                    if test_case.expected_outcome == TestCaseType::NotWf || test_case.expected_outcome == TestCaseType::Error {
-                        println!("------------------\nMISMATCHED OUTCOME\nParsed non-well formed document\nFor test: {:?}", test_case);
+                    let passed = test_case.expected_outcome != TestCaseType::Valid
+                        && test_case.expected_outcome != TestCaseType::Invalid;
+                    report_accumulator.record(CaseResult {
+                        id: test_case.id.clone(),
+                        sections: test_case.sections.clone(),
+                        backend: parser.name().to_string(),
+                        expected_outcome: format!("{:?}", test_case.expected_outcome),
+                        actual_outcome: "parse error".to_string(),
+                        passed,
+                        message: (!passed).then(|| err.to_string()),
+                    });
+                }
+                Ok(()) => {
+                    let passed = test_case.expected_outcome != TestCaseType::NotWf
+                        && test_case.expected_outcome != TestCaseType::Error;
+                    report_accumulator.record(CaseResult {
+                        id: test_case.id.clone(),
+                        sections: test_case.sections.clone(),
+                        backend: parser.name().to_string(),
+                        expected_outcome: format!("{:?}", test_case.expected_outcome),
+                        actual_outcome: "parsed successfully".to_string(),
+                        passed,
+                        message: (!passed).then(|| "parsed a non-well-formed document".to_string()),
+                    });
+                    // `canonical::canonicalize_file` always re-parses with its own
+                    // quick-xml reader, so this check only reflects quick-xml's
+                    // serialization; running it once per backend would just repeat
+                    // the identical diff under every other backend's name.
+                    if test_case.expected_outcome == TestCaseType::Valid
+                        && parser.name() == QuickXmlBackend.name()
+                    {
+                        check_canonical_output(test_case, &file_to_read_path, base_dir, parser.name(), report_accumulator);
                     }
-                    break
-                },
-                _ => {}
+                }
             }
         }
     }
     Ok(())
 }
 
-fn setup_config_file_buf_reader(release_root_path: &Path, conf_sub_path: &str) -> Result<(PathBuf, BufReader<File>), Box<dyn std::error::Error>> {
+/// For `Valid` tests with an `@OUTPUT` reference, re-serialize the parsed
+/// document into canonical form and diff it against that reference.
+///
+/// Only meaningful for the quick-xml backend: `canonical::canonicalize_file`
+/// builds its own quick-xml reader regardless of which backend parsed the
+/// document, so the caller only invokes this once, for `quick-xml`.
+fn check_canonical_output(
+    test_case: &TestCase,
+    file_to_read_path: &Path,
+    base_dir: &Path,
+    backend_name: &str,
+    report_accumulator: &mut ReportAccumulator,
+) {
+    let Some(output_uri) = &test_case.output else {
+        return;
+    };
+    let output_path = base_dir.join(output_uri);
+
+    let record_failure = |report_accumulator: &mut ReportAccumulator, message: String| {
+        report_accumulator.record(CaseResult {
+            id: test_case.id.clone(),
+            sections: test_case.sections.clone(),
+            backend: backend_name.to_string(),
+            expected_outcome: "canonical match".to_string(),
+            actual_outcome: "canonical mismatch".to_string(),
+            passed: false,
+            message: Some(message),
+        });
+    };
+
+    let actual = match canonical::canonicalize_file(file_to_read_path) {
+        Ok(canonical) => canonical,
+        Err(err) => {
+            record_failure(report_accumulator, format!("failed to canonicalize parsed document: {err}"));
+            return;
+        }
+    };
+    // This crate has no DTD support, so a document with a DOCTYPE might rely
+    // on DTD-defaulted attributes that never get materialized into `actual`.
+    // We can't tell a genuine mismatch from that gap, so skip rather than
+    // report a false failure (see `Canonicalized::has_doctype`).
+    if actual.has_doctype {
+        return;
+    }
+    let expected = match canonical::canonicalize_file(&output_path) {
+        Ok(canonical) => canonical,
+        Err(err) => {
+            record_failure(report_accumulator, format!("failed to canonicalize @OUTPUT reference {output_path:?}: {err}"));
+            return;
+        }
+    };
+
+    if let Some(diff) = canonical::diff_canonical(&expected.text, &actual.text) {
+        record_failure(report_accumulator, format!("canonical output did not match @OUTPUT reference\n{diff}"));
+    }
+}
+
+fn read_config_file<'a>(
+    release_root_path: &Path,
+    conf_sub_path: &str,
+    source_cache: &'a mut SourceCache,
+) -> Result<(PathBuf, &'a str), Box<dyn std::error::Error>> {
     let conf_path = release_root_path.join(conf_sub_path);
-    let conf_file_read = File::open(&conf_path)?;
     let conf_file_parent_dir = conf_path.parent().ok_or(format!("no parent for {:?}", conf_path.to_str()))?.to_path_buf();
-    let reader = BufReader::new(conf_file_read);
-    Ok((conf_file_parent_dir, reader))
+    let text = source_cache.read_config_text(&conf_path)?;
+    Ok((conf_file_parent_dir, text))
+}
+
+// One `#[test] fn` per conformance case, generated by `build.rs` from
+// whatever suite `XMLCONF_DIR` pointed at. Empty if it wasn't set.
+#[cfg(test)]
+mod generated_tests {
+    include!(concat!(env!("OUT_DIR"), "/generated_conformance_tests.rs"));
 }
@@ -0,0 +1,128 @@
+//! Collected per-case results and machine-readable report output (JSON and
+//! JUnit XML), replacing the old inline `println!("MISMATCHED OUTCOME...")`.
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseResult {
+    pub id: String,
+    pub sections: String,
+    pub backend: String,
+    pub expected_outcome: String,
+    pub actual_outcome: String,
+    pub passed: bool,
+    pub message: Option<String>,
+}
+
+/// Accumulates results across `run_sun_tests_for`/`run_ibm_tests_for` calls
+/// so they can be reported all at once instead of printed as they happen.
+#[derive(Debug, Default)]
+pub struct ReportAccumulator {
+    pub results: Vec<CaseResult>,
+}
+
+impl ReportAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, result: CaseResult) {
+        self.results.push(result);
+    }
+
+    pub fn failed_count(&self) -> usize {
+        self.results.iter().filter(|r| !r.passed).count()
+    }
+}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum ReportFormat {
+    Json,
+    #[value(name = "junit")]
+    JUnit,
+}
+
+#[derive(Debug)]
+pub enum ReportError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for ReportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportError::Io(err) => write!(f, "{err}"),
+            ReportError::Json(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ReportError {}
+
+impl From<std::io::Error> for ReportError {
+    fn from(err: std::io::Error) -> Self {
+        ReportError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for ReportError {
+    fn from(err: serde_json::Error) -> Self {
+        ReportError::Json(err)
+    }
+}
+
+pub fn write_report(
+    accumulator: &ReportAccumulator,
+    format: ReportFormat,
+    path: &Path,
+) -> Result<(), ReportError> {
+    let mut file = File::create(path)?;
+    match format {
+        ReportFormat::Json => {
+            serde_json::to_writer_pretty(&file, &accumulator.results)?;
+        }
+        ReportFormat::JUnit => {
+            file.write_all(render_junit(accumulator).as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_junit(accumulator: &ReportAccumulator) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuite name=\"xml-conformance-rs\" tests=\"{}\" failures=\"{}\">\n",
+        accumulator.results.len(),
+        accumulator.failed_count(),
+    ));
+    for result in &accumulator.results {
+        out.push_str(&format!(
+            "  <testcase name=\"{}\" classname=\"{}\">\n",
+            escape_xml(&result.id),
+            escape_xml(&result.backend),
+        ));
+        if !result.passed {
+            out.push_str(&format!(
+                "    <failure message=\"expected {} but got {}\">{}</failure>\n",
+                escape_xml(&result.expected_outcome),
+                escape_xml(&result.actual_outcome),
+                escape_xml(result.message.as_deref().unwrap_or("")),
+            ));
+        }
+        out.push_str("  </testcase>\n");
+    }
+    out.push_str("</testsuite>\n");
+    out
+}